@@ -1,10 +1,23 @@
 use log::{debug, warn};
-use std::process::Command;
-use std::time::Duration;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 /// Default timeout for Claude CLI operations (30 seconds)
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// Default headroom added per KB of transcript text beyond the base timeout.
+const DEFAULT_TIMEOUT_PER_KB_SECS: u64 = 1;
+
+/// Default ceiling on the length-adaptive timeout, however large the transcript.
+const DEFAULT_TIMEOUT_MAX_SECS: u64 = 180;
+
+/// How long to wait after a polite termination request before escalating to a kill.
+const TERMINATION_GRACE_SECS: u64 = 2;
+
 /// Default model for Claude CLI (fastest and cheapest)
 pub const DEFAULT_CLAUDE_MODEL: &str = "haiku";
 
@@ -15,76 +28,879 @@ pub const CLAUDE_CLI_MODELS: &[(&str, &str)] = &[
     ("opus", "Opus (most capable)"),
 ];
 
-/// Process text using Claude Code CLI (`claude -p`).
+/// Outcome of asking a still-running child process to stop.
+enum TerminationOutcome {
+    /// The process exited on its own within the grace window after the polite signal.
+    Graceful,
+    /// The process ignored the polite signal and had to be force-killed.
+    ForceKilled,
+}
+
+/// Ask a still-running child to terminate, giving it a short grace window to exit on
+/// its own before escalating. `Child::kill()` maps to an immediate SIGKILL, leaving the
+/// Claude CLI no chance to flush or clean up any child processes it spawned, so on Unix
+/// we send SIGTERM first and only fall back to a hard kill if it's ignored. On other
+/// platforms the first step is already `Child::kill()`, and an exit within the grace
+/// window is still reported as [`TerminationOutcome::Graceful`].
+fn terminate_child(child: &mut std::process::Child) -> TerminationOutcome {
+    #[cfg(unix)]
+    {
+        // SAFETY: `child.id()` is the pid of a process we own and have not yet reaped.
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = child.kill();
+    }
+
+    let grace = Duration::from_secs(TERMINATION_GRACE_SECS);
+    let start = Instant::now();
+    while start.elapsed() < grace {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            return TerminationOutcome::Graceful;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return TerminationOutcome::Graceful;
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+    TerminationOutcome::ForceKilled
+}
+
+/// Outcome label recorded for a single backend CLI call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallOutcome {
+    Completed,
+    Failed,
+    TimedOut,
+}
+
+/// Cap on tracked call durations per model. A long-running dictation session can
+/// rack up thousands of calls; retaining all of them forever would leak memory and
+/// make every stats read sort an ever-growing vector, so only the most recent
+/// `MAX_TRACKED_DURATIONS` are kept and latency stats reflect recent behavior
+/// rather than every call since startup. The outcome counters are not bounded.
+const MAX_TRACKED_DURATIONS: usize = 200;
+
+/// Per-model call history backing [`get_claude_cli_stats`].
+#[derive(Default)]
+struct ModelCallHistory {
+    completed: u64,
+    failed: u64,
+    timed_out: u64,
+    /// Wall-clock duration of the most recent calls, oldest first, bounded to
+    /// `MAX_TRACKED_DURATIONS`, used to compute average and percentile latency.
+    durations: std::collections::VecDeque<Duration>,
+}
+
+impl ModelCallHistory {
+    fn record_duration(&mut self, elapsed: Duration) {
+        if self.durations.len() >= MAX_TRACKED_DURATIONS {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(elapsed);
+    }
+}
+
+fn stats_registry() -> &'static Mutex<HashMap<String, ModelCallHistory>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, ModelCallHistory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Drop guard that times a single CLI call and records its outcome into the global
+/// stats registry when it goes out of scope, regardless of which `return` path is
+/// taken. Defaults to `Failed` so an early `?`-propagated error is still counted
+/// correctly unless a later code path marks it `Completed` or `TimedOut`.
+struct CallMetricsGuard {
+    model: String,
+    start: Instant,
+    outcome: CallOutcome,
+}
+
+impl CallMetricsGuard {
+    fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            start: Instant::now(),
+            outcome: CallOutcome::Failed,
+        }
+    }
+
+    fn mark(&mut self, outcome: CallOutcome) {
+        self.outcome = outcome;
+    }
+}
+
+impl Drop for CallMetricsGuard {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let mut registry = match stats_registry().lock() {
+            Ok(registry) => registry,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let history = registry.entry(self.model.clone()).or_default();
+        match self.outcome {
+            CallOutcome::Completed => history.completed += 1,
+            CallOutcome::Failed => history.failed += 1,
+            CallOutcome::TimedOut => history.timed_out += 1,
+        }
+        history.record_duration(elapsed);
+        debug!(
+            "Claude CLI call for model '{}' took {:?} ({:?})",
+            self.model, elapsed, self.outcome
+        );
+    }
+}
+
+/// Latency and outcome summary for a single model, returned by [`get_claude_cli_stats`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ClaudeCliModelStats {
+    pub model: String,
+    pub call_count: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub timed_out: u64,
+    pub success_rate: f64,
+    pub avg_latency_ms: u64,
+    pub p50_latency_ms: u64,
+    pub p95_latency_ms: u64,
+}
+
+fn percentile_ms(sorted_durations: &[Duration], pct: f64) -> u64 {
+    if sorted_durations.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted_durations.len() - 1) as f64 * pct).round() as usize;
+    sorted_durations[rank].as_millis() as u64
+}
+
+/// Snapshot call-count, latency, and success-rate stats per `CLAUDE_CLI_MODELS` entry,
+/// followed by any other recorded label (sorted), so the frontend can show e.g. "opus is timing out 40% of the time" instead of the
+/// user having to guess which model to switch to.
+pub fn get_claude_cli_stats() -> Vec<ClaudeCliModelStats> {
+    let registry = match stats_registry().lock() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    // Every built-in Claude model is always listed, even with zero calls, so the
+    // frontend can show the full model picker. Any other registry key (e.g. a
+    // non-Claude backend's "backend:model" label from `CliBackend`) is appended
+    // after, so those calls remain visible instead of accumulating in the registry
+    // under a key this function never reads.
+    let mut seen: std::collections::HashSet<&str> = std::collections::HashSet::new();
+    let mut stats: Vec<ClaudeCliModelStats> = CLAUDE_CLI_MODELS
+        .iter()
+        .map(|(id, _label)| {
+            seen.insert(id);
+            model_stats(id, registry.get(*id))
+        })
+        .collect();
+
+    let mut extra_keys: Vec<&String> = registry.keys().filter(|key| !seen.contains(key.as_str())).collect();
+    extra_keys.sort();
+    stats.extend(extra_keys.into_iter().map(|key| model_stats(key, registry.get(key))));
+
+    stats
+}
+
+fn model_stats(label: &str, history: Option<&ModelCallHistory>) -> ClaudeCliModelStats {
+    let Some(history) = history else {
+        return ClaudeCliModelStats {
+            model: label.to_string(),
+            call_count: 0,
+            completed: 0,
+            failed: 0,
+            timed_out: 0,
+            success_rate: 0.0,
+            avg_latency_ms: 0,
+            p50_latency_ms: 0,
+            p95_latency_ms: 0,
+        };
+    };
+
+    let call_count = history.completed + history.failed + history.timed_out;
+    let success_rate = if call_count == 0 {
+        0.0
+    } else {
+        history.completed as f64 / call_count as f64
+    };
+    let mut sorted_durations: Vec<Duration> = history.durations.iter().copied().collect();
+    sorted_durations.sort();
+    let avg_latency_ms = if sorted_durations.is_empty() {
+        0
+    } else {
+        let total_ms: u128 = sorted_durations.iter().map(|d| d.as_millis()).sum();
+        (total_ms / sorted_durations.len() as u128) as u64
+    };
+
+    ClaudeCliModelStats {
+        model: label.to_string(),
+        call_count,
+        completed: history.completed,
+        failed: history.failed,
+        timed_out: history.timed_out,
+        success_rate,
+        avg_latency_ms,
+        p50_latency_ms: percentile_ms(&sorted_durations, 0.50),
+        p95_latency_ms: percentile_ms(&sorted_durations, 0.95),
+    }
+}
+
+/// User-adjustable policy for how long a CLI call may run before being terminated.
+/// A fixed 30-second timeout is too short for long transcripts on a slow model and
+/// wastefully long for a two-word `haiku` correction, so the effective timeout scales
+/// with input size: `base_secs` covers fixed startup/response overhead, `per_kb_secs`
+/// adds headroom per (rounded-up) KB of transcript text, and `max_secs` bounds the
+/// total so a pathological transcript can't block indefinitely.
+///
+/// `Serialize`/`Deserialize` make this ready to round-trip through a settings store;
+/// there's no settings module in this tree yet, so callers still construct it via
+/// [`TimeoutPolicy::default`] or explicit fields rather than loading it from disk.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct TimeoutPolicy {
+    pub base_secs: u64,
+    pub per_kb_secs: u64,
+    pub max_secs: u64,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            base_secs: DEFAULT_TIMEOUT_SECS,
+            per_kb_secs: DEFAULT_TIMEOUT_PER_KB_SECS,
+            max_secs: DEFAULT_TIMEOUT_MAX_SECS,
+        }
+    }
+}
+
+impl TimeoutPolicy {
+    /// Resolve the effective timeout in seconds for a transcript of `text_len` bytes.
+    fn resolve(&self, text_len: usize) -> u64 {
+        let kb = (text_len as u64).div_ceil(1024);
+        (self.base_secs + self.per_kb_secs * kb).min(self.max_secs)
+    }
+}
+
+/// How a backend expects the instruction prompt and transcript text to be delivered
+/// to its CLI, alongside the model-selection flags on argv.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PromptDelivery {
+    /// The prompt is appended to argv as its own argument; the transcript text is
+    /// piped to stdin. Used by CLIs with a dedicated "run this instruction" flag.
+    ArgvPromptStdinText,
+    /// Prompt and transcript are joined into one message and piped to stdin; argv
+    /// carries only the model selection.
+    CombinedStdin,
+}
+
+/// Describes how to spawn a backend's CLI: the executable, a fixed argv template
+/// (`"{model}"` is substituted with the resolved model id), how the prompt is
+/// delivered, and the flag used to probe availability.
+#[derive(Debug, Clone)]
+pub struct BackendSpec {
+    pub executable: String,
+    pub argv_template: Vec<String>,
+    pub prompt_delivery: PromptDelivery,
+    pub version_args: Vec<String>,
+}
+
+impl BackendSpec {
+    fn resolve_argv(&self, model: &str) -> Vec<String> {
+        self.argv_template
+            .iter()
+            .map(|arg| arg.replace("{model}", model))
+            .collect()
+    }
+}
+
+/// Spawn templates for the backend ids Handy ships with. The generic `"command"`
+/// backend is not here: its template is built from user config in
+/// [`create_text_processor`] instead of a fixed default.
+fn builtin_backend_spec(backend_id: &str) -> Option<BackendSpec> {
+    match backend_id {
+        "claude" => Some(BackendSpec {
+            executable: "claude".to_string(),
+            argv_template: vec!["--model".to_string(), "{model}".to_string(), "-p".to_string()],
+            prompt_delivery: PromptDelivery::ArgvPromptStdinText,
+            version_args: vec!["--version".to_string()],
+        }),
+        "ollama" => Some(BackendSpec {
+            executable: "ollama".to_string(),
+            argv_template: vec!["run".to_string(), "{model}".to_string()],
+            prompt_delivery: PromptDelivery::CombinedStdin,
+            version_args: vec!["--version".to_string()],
+        }),
+        "llm" => Some(BackendSpec {
+            executable: "llm".to_string(),
+            argv_template: vec!["-m".to_string(), "{model}".to_string()],
+            prompt_delivery: PromptDelivery::CombinedStdin,
+            version_args: vec!["--version".to_string()],
+        }),
+        _ => None,
+    }
+}
+
+/// Model catalog reported by a built-in backend. Unlike Claude's fixed
+/// `CLAUDE_CLI_MODELS`, Ollama and `llm` models are normally whatever the user has
+/// pulled locally, so there's nothing meaningful to list ahead of time.
+fn builtin_backend_models(backend_id: &str) -> Vec<(String, String)> {
+    match backend_id {
+        "claude" => get_claude_cli_models(),
+        _ => Vec::new(),
+    }
+}
+
+/// A pluggable backend capable of running dictated text through an external LLM CLI
+/// to fix grammar/punctuation. The Claude Code CLI is one concrete backend among
+/// several; `create_text_processor` builds whichever one the user has configured.
+pub trait TextProcessor {
+    /// Run `text` through the backend using `prompt` as the instruction.
+    /// Returns Ok(processed_text) on success, or Err with a message explaining why;
+    /// callers should fall back to the original text on error.
+    fn process(&self, text: &str, prompt: &str) -> Result<String, String>;
+
+    /// Probe whether the backend's CLI is installed and runnable.
+    fn is_available(&self) -> bool;
+
+    /// Models this backend is known to support, as (id, human label) pairs.
+    fn models(&self) -> Vec<(String, String)>;
+}
+
+/// A [`TextProcessor`] driven entirely by a [`BackendSpec`]: Claude, Ollama, `llm`,
+/// and user-defined `command` backends are all one of these configured differently,
+/// so the spawn/pipe/timeout/termination machinery only has to be implemented once.
+pub struct CliBackend {
+    backend_id: String,
+    spec: BackendSpec,
+    model: String,
+    models: Vec<(String, String)>,
+    timeout: TimeoutPolicy,
+}
+
+impl TextProcessor for CliBackend {
+    fn process(&self, text: &str, prompt: &str) -> Result<String, String> {
+        // Claude keeps going through `process_with_claude_cli_timeout` directly so its
+        // stats stay keyed by plain model id, matching `get_claude_cli_stats`'s lookup
+        // against `CLAUDE_CLI_MODELS`.
+        if self.backend_id == "claude" {
+            return process_with_claude_cli_timeout(text, prompt, &self.model, &self.timeout);
+        }
+
+        if text.trim().is_empty() {
+            return Ok(text.to_string());
+        }
+
+        let mut argv = self.spec.resolve_argv(&self.model);
+        let stdin_text = match self.spec.prompt_delivery {
+            PromptDelivery::ArgvPromptStdinText => {
+                argv.push(prompt.to_string());
+                text.to_string()
+            }
+            PromptDelivery::CombinedStdin => format!("{}\n\nText:\n{}", prompt, text),
+        };
+
+        let timeout_secs = self.timeout.resolve(text.len());
+        debug!(
+            "Resolved timeout for '{}' backend: {}s (base {}s + {}s/KB, max {}s)",
+            self.backend_id, timeout_secs, self.timeout.base_secs, self.timeout.per_kb_secs, self.timeout.max_secs
+        );
+
+        let metrics_label = format!("{}:{}", self.backend_id, self.model);
+        let mut metrics = CallMetricsGuard::new(&metrics_label);
+        let result = run_cli(&self.spec.executable, &argv, Some(&stdin_text), &mut metrics, timeout_secs)?;
+        if result.is_empty() {
+            debug!("{} backend returned empty response, using original text", self.backend_id);
+            return Ok(text.to_string());
+        }
+        Ok(result)
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new(&self.spec.executable)
+            .args(&self.spec.version_args)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn models(&self) -> Vec<(String, String)> {
+        self.models.clone()
+    }
+}
+
+/// User-facing configuration selecting which backend and model to run dictated text
+/// through. `custom_executable`/`custom_argv_template` are only consulted for the
+/// generic `"command"` backend id. Serializable so it can be persisted as a single
+/// unit once a settings store exists to read/write it; persistence itself isn't
+/// wired up here.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TextProcessorConfig {
+    pub backend_id: String,
+    pub model: String,
+    pub custom_executable: Option<String>,
+    pub custom_argv_template: Option<Vec<String>>,
+    /// How the `"command"` backend delivers the prompt and transcript text.
+    /// Defaults to [`PromptDelivery::CombinedStdin`] when unset.
+    pub custom_prompt_delivery: Option<PromptDelivery>,
+    /// Argv used to probe the `"command"` backend's availability.
+    /// Defaults to `["--version"]` when unset.
+    pub custom_version_args: Option<Vec<String>>,
+    pub timeout: TimeoutPolicy,
+}
+
+/// Build the `TextProcessor` named in `config`. Built-in ids (`"claude"`, `"ollama"`,
+/// `"llm"`) use their fixed spawn templates; `"command"` builds its template from
+/// `config.custom_executable`/`config.custom_argv_template`/`config.custom_prompt_delivery`/
+/// `config.custom_version_args`, so a user running a local model through e.g.
+/// `ollama run` under a wrapper script gets the same grammar-fixing feature without
+/// Handy needing to know about their setup ahead of time.
+pub fn create_text_processor(config: &TextProcessorConfig) -> Result<Box<dyn TextProcessor>, String> {
+    let timeout = config.timeout;
+
+    if config.backend_id == "command" {
+        let executable = config
+            .custom_executable
+            .clone()
+            .filter(|e| !e.is_empty())
+            .ok_or_else(|| "The \"command\" backend requires a custom executable".to_string())?;
+        let argv_template = config
+            .custom_argv_template
+            .clone()
+            .ok_or_else(|| "The \"command\" backend requires an argv template".to_string())?;
+        let prompt_delivery = config.custom_prompt_delivery.unwrap_or(PromptDelivery::CombinedStdin);
+        let version_args = config
+            .custom_version_args
+            .clone()
+            .unwrap_or_else(|| vec!["--version".to_string()]);
+
+        let spec = BackendSpec {
+            executable,
+            argv_template,
+            prompt_delivery,
+            version_args,
+        };
+        return Ok(Box::new(CliBackend {
+            backend_id: "command".to_string(),
+            spec,
+            model: config.model.clone(),
+            models: Vec::new(),
+            timeout,
+        }));
+    }
+
+    let spec = builtin_backend_spec(&config.backend_id)
+        .ok_or_else(|| format!("Unknown text processor backend: {}", config.backend_id))?;
+    let models = builtin_backend_models(&config.backend_id);
+    Ok(Box::new(CliBackend {
+        backend_id: config.backend_id.clone(),
+        spec,
+        model: config.model.clone(),
+        models,
+        timeout,
+    }))
+}
+
+/// Spawn `executable` with `argv`, optionally piping `stdin_text` into its stdin, and
+/// wait up to `timeout_secs` before terminating it. Shared by every [`TextProcessor`]
+/// backend via [`CliBackend`] as well as [`process_with_claude_cli`]. `metrics` is
+/// owned by the caller rather than created here, so a caller that retries through
+/// multiple strategies for one logical request (e.g. streaming falling back to a
+/// buffered call) can record a single outcome instead of one per attempt.
+fn run_cli(
+    executable: &str,
+    argv: &[String],
+    stdin_text: Option<&str>,
+    metrics: &mut CallMetricsGuard,
+    timeout_secs: u64,
+) -> Result<String, String> {
+    debug!(
+        "Calling '{}' with argv {:?}, stdin length: {} chars",
+        executable,
+        argv,
+        stdin_text.map(|t| t.len()).unwrap_or(0)
+    );
+
+    // The transcript body is piped through stdin rather than passed on argv: argv is
+    // subject to ARG_MAX (~128-256 KB on many systems), which a long dictation session
+    // can easily exceed, while stdin has no such limit.
+    let mut command = Command::new(executable);
+    command
+        .args(argv)
+        .stdin(if stdin_text.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", executable, e))?;
+
+    // Writing the transcript to stdin and reading stdout must happen concurrently:
+    // if the transcript is larger than the OS pipe buffer, writing it all before
+    // reading stdout would block on a full pipe while the child blocks writing to a
+    // full stdout pipe, deadlocking both sides.
+    let mut stdout = child.stdout.take().expect("child stdout was piped");
+    let mut stderr = child.stderr.take().expect("child stderr was piped");
+
+    let writer = stdin_text.map(|text| {
+        let mut stdin = child.stdin.take().expect("child stdin was piped");
+        let text_owned = text.to_string();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(text_owned.as_bytes());
+            // Drop closes stdin, signaling EOF to the child.
+        })
+    });
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    // Wait with timeout
+    let timeout = Duration::from_secs(timeout_secs);
+    let start = Instant::now();
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {
+                if start.elapsed() > timeout {
+                    metrics.mark(CallOutcome::TimedOut);
+                    let outcome = terminate_child(&mut child);
+                    if let Some(writer) = writer {
+                        let _ = writer.join();
+                    }
+                    let _ = stdout_reader.join();
+                    let _ = stderr_reader.join();
+                    let outcome_desc = match outcome {
+                        TerminationOutcome::Graceful => "terminated gracefully",
+                        TerminationOutcome::ForceKilled => "had to be force-killed",
+                    };
+                    return Err(format!(
+                        "{} timed out after {} seconds ({})",
+                        executable, timeout_secs, outcome_desc
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => {
+                return Err(format!("Error waiting for {}: {}", executable, e));
+            }
+        }
+    };
+
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    let stdout_bytes = stdout_reader.join().unwrap_or_default();
+    let stderr_bytes = stderr_reader.join().unwrap_or_default();
+
+    if status.success() {
+        metrics.mark(CallOutcome::Completed);
+        Ok(String::from_utf8_lossy(&stdout_bytes).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&stderr_bytes);
+        Err(format!("{} failed: {}", executable, stderr))
+    }
+}
+
+/// Process text using Claude Code CLI (`claude -p`), using the default [`TimeoutPolicy`].
 /// Returns Ok(processed_text) on success, or Err with error message.
 /// IMPORTANT: On error, caller should fall back to original text.
 pub fn process_with_claude_cli(text: &str, prompt: &str, model: &str) -> Result<String, String> {
+    process_with_claude_cli_timeout(text, prompt, model, &TimeoutPolicy::default())
+}
+
+/// Process text using Claude Code CLI (`claude -p`), resolving the call timeout from
+/// `timeout` and `text`'s length rather than a fixed constant.
+/// Returns Ok(processed_text) on success, or Err with error message.
+/// IMPORTANT: On error, caller should fall back to original text.
+pub fn process_with_claude_cli_timeout(
+    text: &str,
+    prompt: &str,
+    model: &str,
+    timeout: &TimeoutPolicy,
+) -> Result<String, String> {
     if text.trim().is_empty() {
         return Ok(text.to_string());
     }
-
-    let full_prompt = format!("{}\n\nText:\n{}", prompt, text);
     let model_to_use = if model.is_empty() { DEFAULT_CLAUDE_MODEL } else { model };
+    let mut metrics = CallMetricsGuard::new(model_to_use);
+    claude_cli_call(text, prompt, model_to_use, timeout, &mut metrics)
+}
 
+/// Core of [`process_with_claude_cli_timeout`], with the metrics guard owned by the
+/// caller. Also used by [`process_with_claude_cli_streaming`]'s buffered fallback so
+/// that attempt is recorded under the same guard as the streaming attempt that
+/// preceded it, rather than as a second, separate call.
+fn claude_cli_call(
+    text: &str,
+    prompt: &str,
+    model_to_use: &str,
+    timeout: &TimeoutPolicy,
+    metrics: &mut CallMetricsGuard,
+) -> Result<String, String> {
+    let spec = builtin_backend_spec("claude").expect("claude backend spec is always registered");
+    let mut argv = spec.resolve_argv(model_to_use);
+    argv.push(prompt.to_string());
+
+    let timeout_secs = timeout.resolve(text.len());
     debug!(
-        "Calling Claude CLI with model '{}', prompt length: {} chars",
+        "Calling Claude CLI with model '{}', prompt length: {} chars, text length: {} chars, resolved timeout: {}s (base {}s + {}s/KB, max {}s)",
         model_to_use,
-        full_prompt.len()
+        prompt.len(),
+        text.len(),
+        timeout_secs,
+        timeout.base_secs,
+        timeout.per_kb_secs,
+        timeout.max_secs
     );
 
-    // Use spawn + wait_with_output for timeout support
-    let mut child = Command::new("claude")
-        .arg("--model")
-        .arg(model_to_use)
-        .arg("-p")
-        .arg(&full_prompt)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
+    let result = run_cli(&spec.executable, &argv, Some(text), metrics, timeout_secs)?;
+    if result.is_empty() {
+        debug!("Claude CLI returned empty response, using original text");
+        return Ok(text.to_string());
+    }
+    debug!("Claude CLI succeeded, output length: {} chars", result.len());
+    Ok(result)
+}
+
+/// Process text using Claude Code CLI, invoking `on_chunk` with each incremental
+/// text delta as the model produces it rather than blocking until the whole response
+/// is buffered. Lets the dictation UI show grammar-corrected text appearing
+/// progressively instead of freezing for up to the call's timeout.
+///
+/// Falls back to the buffered [`process_with_claude_cli`] path whenever the streaming
+/// attempt returns an error: the CLI can't be spawned, a stream event fails to parse,
+/// the call times out, or the CLI exits non-zero.
+///
+/// A streaming attempt that falls back to the buffered call is still one logical
+/// request: both attempts share a single [`CallMetricsGuard`] so the registry
+/// records one outcome, not two.
+pub fn process_with_claude_cli_streaming(
+    text: &str,
+    prompt: &str,
+    model: &str,
+    mut on_chunk: impl FnMut(&str),
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Ok(text.to_string());
+    }
+
+    let model_to_use = if model.is_empty() { DEFAULT_CLAUDE_MODEL } else { model };
+    let timeout = TimeoutPolicy::default();
+    let timeout_secs = timeout.resolve(text.len());
+    let mut metrics = CallMetricsGuard::new(model_to_use);
+
+    match run_claude_cli_streaming(text, prompt, model_to_use, timeout_secs, &mut on_chunk) {
+        Ok(result) => {
+            metrics.mark(CallOutcome::Completed);
+            if result.is_empty() {
+                debug!("Claude CLI streaming returned empty response, using original text");
+                return Ok(text.to_string());
+            }
+            Ok(result)
+        }
+        Err(e) => {
+            warn!("Claude CLI streaming failed ({}), falling back to buffered call", e);
+            claude_cli_call(text, prompt, model_to_use, &timeout, &mut metrics)
+        }
+    }
+}
+
+/// One event parsed from a `claude --output-format stream-json` line. The CLI emits
+/// typed `system`/`assistant`/`result` events; an incremental text delta is only
+/// present when `--include-partial-messages` is set, wrapped as
+/// `{"type":"stream_event","event":{"type":"content_block_delta","delta":{"text":...}}}`
+/// (the underlying Anthropic Messages-API stream event). The final `result` event
+/// carries the complete, authoritative response text regardless of whether any
+/// deltas were emitted, so it's preferred over the accumulated deltas when present.
+enum StreamEvent {
+    Delta(String),
+    Result(String),
+    Other,
+}
+
+fn parse_stream_event(line: &str) -> Result<StreamEvent, String> {
+    let event: Value =
+        serde_json::from_str(line).map_err(|e| format!("Failed to parse stream event: {}", e))?;
+
+    if let Some(text) = event.pointer("/event/delta/text").and_then(Value::as_str) {
+        return Ok(StreamEvent::Delta(text.to_string()));
+    }
+    if event.get("type").and_then(Value::as_str) == Some("result")
+        && let Some(result) = event.get("result").and_then(Value::as_str)
+    {
+        return Ok(StreamEvent::Result(result.to_string()));
+    }
+    Ok(StreamEvent::Other)
+}
+
+/// Streaming counterpart to [`run_cli`]: spawns `claude` with `--output-format
+/// stream-json`, forwarding each parsed text delta to `on_chunk` as it arrives
+/// instead of buffering the whole response. Reuses the same try_wait/timeout/
+/// `terminate_child` shape as `run_cli` so a hung `claude` process is still bounded
+/// by `timeout_secs`, just driven from a side channel instead of stdout directly
+/// since stdout here is read line-by-line on its own thread.
+fn run_claude_cli_streaming(
+    text: &str,
+    prompt: &str,
+    model: &str,
+    timeout_secs: u64,
+    on_chunk: &mut impl FnMut(&str),
+) -> Result<String, String> {
+    let spec = builtin_backend_spec("claude").expect("claude backend spec is always registered");
+    let mut argv = spec.resolve_argv(model);
+    argv.push(prompt.to_string());
+    argv.push("--output-format".to_string());
+    argv.push("stream-json".to_string());
+    argv.push("--include-partial-messages".to_string());
+    // `-p` combined with `--output-format stream-json` requires `--verbose`; the CLI
+    // exits non-zero without it.
+    argv.push("--verbose".to_string());
+
+    let mut child = Command::new(&spec.executable)
+        .args(&argv)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| format!("Failed to spawn Claude CLI: {}", e))?;
 
-    // Wait with timeout
-    let timeout = Duration::from_secs(DEFAULT_TIMEOUT_SECS);
-    let start = std::time::Instant::now();
+    let mut stdin = child.stdin.take().expect("child stdin was piped");
+    let stdout = child.stdout.take().expect("child stdout was piped");
+    let mut stderr = child.stderr.take().expect("child stderr was piped");
 
-    loop {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                // Process has exited
-                let output = child
-                    .wait_with_output()
-                    .map_err(|e| format!("Failed to read Claude CLI output: {}", e))?;
-
-                if status.success() {
-                    let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if result.is_empty() {
-                        debug!("Claude CLI returned empty response, using original text");
-                        return Ok(text.to_string());
-                    }
-                    debug!("Claude CLI succeeded, output length: {} chars", result.len());
-                    return Ok(result);
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return Err(format!("Claude CLI failed: {}", stderr));
-                }
+    let text_owned = text.to_string();
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(text_owned.as_bytes());
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    // Stdout is drained line-by-line on its own thread and forwarded over a channel,
+    // the same decoupling `run_cli` gets for free from `read_to_end` on a thread:
+    // it lets this function poll `try_wait()` with a timeout on the main thread
+    // instead of blocking here until EOF, which would hang forever against a `claude`
+    // process that stops producing output without exiting.
+    let (line_tx, line_rx) = mpsc::channel::<String>();
+    let stdout_reader = std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line_tx.send(line).is_err() {
+                break;
             }
+        }
+    });
+
+    let mut full_text = String::new();
+    let mut final_result: Option<String> = None;
+    let mut apply_event = |event: StreamEvent, full_text: &mut String, final_result: &mut Option<String>| match event {
+        StreamEvent::Delta(delta) => {
+            on_chunk(&delta);
+            full_text.push_str(&delta);
+        }
+        StreamEvent::Result(result) => *final_result = Some(result),
+        StreamEvent::Other => {}
+    };
+
+    let timeout = Duration::from_secs(timeout_secs);
+    let start = Instant::now();
+
+    // Once the child is running, every error exit must terminate it and join the
+    // three threads before returning: `Child`'s drop does not kill the process, so
+    // returning early via `?` would orphan a still-running `claude`, leak the
+    // writer/stdout/stderr threads, and -- since the caller falls back to a second,
+    // buffered call on any `Err` here -- race a second concurrent `claude` call
+    // against the abandoned first one.
+    let status = loop {
+        for line in line_rx.try_iter() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = match parse_stream_event(&line) {
+                Ok(event) => event,
+                Err(e) => {
+                    let _ = terminate_child(&mut child);
+                    let _ = writer.join();
+                    let _ = stdout_reader.join();
+                    let _ = stderr_reader.join();
+                    return Err(e);
+                }
+            };
+            apply_event(event, &mut full_text, &mut final_result);
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
             Ok(None) => {
-                // Process still running
                 if start.elapsed() > timeout {
-                    // Kill the process
-                    let _ = child.kill();
+                    let outcome = terminate_child(&mut child);
+                    let _ = writer.join();
+                    let _ = stdout_reader.join();
+                    let _ = stderr_reader.join();
+                    let outcome_desc = match outcome {
+                        TerminationOutcome::Graceful => "terminated gracefully",
+                        TerminationOutcome::ForceKilled => "had to be force-killed",
+                    };
                     return Err(format!(
-                        "Claude CLI timed out after {} seconds",
-                        DEFAULT_TIMEOUT_SECS
+                        "Claude CLI streaming timed out after {} seconds ({})",
+                        timeout_secs, outcome_desc
                     ));
                 }
-                // Sleep briefly before checking again
-                std::thread::sleep(Duration::from_millis(100));
+                std::thread::sleep(Duration::from_millis(50));
             }
             Err(e) => {
+                let _ = terminate_child(&mut child);
+                let _ = writer.join();
+                let _ = stdout_reader.join();
+                let _ = stderr_reader.join();
                 return Err(format!("Error waiting for Claude CLI: {}", e));
             }
         }
+    };
+
+    // The child has already exited (and been reaped via `try_wait`) to reach this
+    // point, so there's no process left to terminate, but the reader/writer
+    // threads still need joining on every path before returning.
+    for line in line_rx.try_iter() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = match parse_stream_event(&line) {
+            Ok(event) => event,
+            Err(e) => {
+                let _ = writer.join();
+                let _ = stdout_reader.join();
+                let _ = stderr_reader.join();
+                return Err(e);
+            }
+        };
+        apply_event(event, &mut full_text, &mut final_result);
+    }
+
+    let _ = writer.join();
+    let _ = stdout_reader.join();
+    let stderr_bytes = stderr_reader.join().unwrap_or_default();
+
+    if status.success() {
+        Ok(final_result.unwrap_or(full_text).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&stderr_bytes);
+        Err(format!("Claude CLI failed: {}", stderr))
     }
 }
 
@@ -134,4 +950,111 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "   ");
     }
+
+    #[test]
+    fn test_parse_stream_event_partial_delta() {
+        let line = r#"{"type":"stream_event","event":{"type":"content_block_delta","delta":{"type":"text_delta","text":"hel"}}}"#;
+        match parse_stream_event(line).unwrap() {
+            StreamEvent::Delta(text) => assert_eq!(text, "hel"),
+            _ => panic!("expected a delta event"),
+        }
+    }
+
+    #[test]
+    fn test_parse_stream_event_result() {
+        let line = r#"{"type":"result","subtype":"success","result":"hello there"}"#;
+        match parse_stream_event(line).unwrap() {
+            StreamEvent::Result(text) => assert_eq!(text, "hello there"),
+            _ => panic!("expected a result event"),
+        }
+    }
+
+    #[test]
+    fn test_parse_stream_event_system_init_is_ignored() {
+        let line = r#"{"type":"system","subtype":"init","session_id":"abc"}"#;
+        assert!(matches!(parse_stream_event(line).unwrap(), StreamEvent::Other));
+    }
+
+    #[test]
+    fn test_parse_stream_event_invalid_json_errors() {
+        assert!(parse_stream_event("not json").is_err());
+    }
+
+    #[test]
+    fn test_percentile_ms_empty_is_zero() {
+        assert_eq!(percentile_ms(&[], 0.50), 0);
+    }
+
+    #[test]
+    fn test_percentile_ms_rank_rounding() {
+        let durations: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile_ms(&durations, 0.0), 1);
+        assert_eq!(percentile_ms(&durations, 1.0), 10);
+        // rank = round(9 * 0.5) = round(4.5) = 5 -> durations[5] = 6ms
+        assert_eq!(percentile_ms(&durations, 0.50), 6);
+    }
+
+    #[test]
+    fn test_model_call_history_bounds_durations() {
+        let mut history = ModelCallHistory::default();
+        for i in 0..MAX_TRACKED_DURATIONS + 10 {
+            history.record_duration(Duration::from_millis(i as u64));
+        }
+        assert_eq!(history.durations.len(), MAX_TRACKED_DURATIONS);
+        assert_eq!(history.durations.front(), Some(&Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_timeout_policy_resolve_rounds_up_to_next_kb() {
+        let policy = TimeoutPolicy {
+            base_secs: 30,
+            per_kb_secs: 1,
+            max_secs: 180,
+        };
+        assert_eq!(policy.resolve(0), 30);
+        assert_eq!(policy.resolve(1), 31);
+        assert_eq!(policy.resolve(1024), 31);
+        assert_eq!(policy.resolve(1025), 32);
+    }
+
+    #[test]
+    fn test_timeout_policy_resolve_clamps_to_max() {
+        let policy = TimeoutPolicy {
+            base_secs: 30,
+            per_kb_secs: 1,
+            max_secs: 180,
+        };
+        assert_eq!(policy.resolve(1024 * 1000), 180);
+    }
+
+    #[test]
+    fn test_timeout_policy_default_matches_constants() {
+        let policy = TimeoutPolicy::default();
+        assert_eq!(policy.base_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(policy.per_kb_secs, DEFAULT_TIMEOUT_PER_KB_SECS);
+        assert_eq!(policy.max_secs, DEFAULT_TIMEOUT_MAX_SECS);
+    }
+
+    #[test]
+    fn test_resolve_argv_substitutes_model_placeholder() {
+        let spec = builtin_backend_spec("claude").unwrap();
+        assert_eq!(spec.resolve_argv("opus"), vec!["--model", "opus", "-p"]);
+    }
+
+    #[test]
+    fn test_resolve_argv_leaves_non_placeholder_args_untouched() {
+        let spec = builtin_backend_spec("ollama").unwrap();
+        assert_eq!(spec.resolve_argv("llama3"), vec!["run", "llama3"]);
+    }
+
+    #[test]
+    fn test_resolve_argv_with_no_placeholder_is_unchanged() {
+        let spec = BackendSpec {
+            executable: "echo".to_string(),
+            argv_template: vec!["--fixed-flag".to_string()],
+            prompt_delivery: PromptDelivery::CombinedStdin,
+            version_args: vec!["--version".to_string()],
+        };
+        assert_eq!(spec.resolve_argv("unused"), vec!["--fixed-flag"]);
+    }
 }